@@ -21,46 +21,218 @@ impl Blocker for BuiltinBlocklist {
     }
 }
 #[cfg(feature = "reqwest")]
+use base64::{engine::general_purpose::STANDARD_NO_PAD as base64engine, Engine};
+#[cfg(feature = "reqwest")]
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
 /// Fetch a remote page with a blocked base64-encoded seed on each line.
 /// This is nice because you don't actually have to host a server that validates licenses, you can just host this on pastebin or something.
+///
+/// By default the list is fetched fresh on every `check_block` and trusted as plaintext; call
+/// `with_signing_public_key` to require the hosted file to end with a detached Ed25519 signature
+/// line over the preceding body (so a compromised host can't silently un-revoke seeds), and
+/// `with_ttl` to cache the fetched list in memory instead of re-downloading on every check.
+#[cfg(feature = "reqwest")]
 pub struct RemoteFileBlocker {
     pub url: reqwest::Url,
+    /// How long a successfully fetched list is trusted before it's re-fetched. Defaults to 0,
+    /// i.e. every `check_block` hits the network.
+    pub ttl: Duration,
+    /// When the list can't be fetched (network error or non-2xx status), `true` lets the check
+    /// pass rather than blocking every license; `false` (the default) blocks on fetch failure.
+    /// A list that *was* fetched but fails to parse or verify is always treated as blocked,
+    /// regardless of this setting, since that indicates tampering rather than unavailability.
+    pub fail_open: bool,
+    /// When set, the hosted file's last line must be a valid detached Ed25519 signature (over
+    /// the preceding lines) made by this key, or the list is rejected with `BadList`.
+    #[cfg(feature = "ed25519")]
+    pub signing_public_key: Option<[u8; 32]>,
+    cache: Mutex<Option<CachedList>>,
 }
+
 #[cfg(feature = "reqwest")]
-use base64::{engine::general_purpose::STANDARD_NO_PAD as base64engine, Engine};
+struct CachedList {
+    seeds: Vec<Vec<u8>>,
+    etag: Option<String>,
+    fetched_at: Instant,
+}
+
 #[cfg(feature = "reqwest")]
+impl RemoteFileBlocker {
+    /// Fetches fresh on every check (no caching) and fails closed (blocks) if the list can't be
+    /// fetched. Chain `with_ttl`, `with_fail_open`, and/or `with_signing_public_key` to change that.
+    pub fn new(url: reqwest::Url) -> Self {
+        Self {
+            url,
+            ttl: Duration::ZERO,
+            fail_open: false,
+            #[cfg(feature = "ed25519")]
+            signing_public_key: None,
+            cache: Mutex::new(None),
+        }
+    }
+    /// Caches a successfully fetched list in memory for `ttl` before re-fetching.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+    /// Lets `check_block` pass when the list can't be fetched, instead of blocking every license.
+    pub fn with_fail_open(mut self, fail_open: bool) -> Self {
+        self.fail_open = fail_open;
+        self
+    }
+    /// Requires the hosted list to carry a trailing detached Ed25519 signature made by this key.
+    #[cfg(feature = "ed25519")]
+    pub fn with_signing_public_key(mut self, public_key: [u8; 32]) -> Self {
+        self.signing_public_key = Some(public_key);
+        self
+    }
+    fn fetch(&self, etag: Option<&str>) -> Result<FetchOutcome, FetchError> {
+        let mut request = reqwest::blocking::Client::new().get(self.url.clone());
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let response = request.send().map_err(|_| FetchError::Unavailable)?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            // Only trust a 304 if we actually sent If-None-Match: an unsolicited one (e.g. on the
+            // very first, cache-less fetch) means the host is misbehaving, not that our cache is
+            // still fresh, and must not be treated as "reuse whatever's cached" when nothing is.
+            return if etag.is_some() {
+                Ok(FetchOutcome::NotModified)
+            } else {
+                Err(FetchError::Invalid)
+            };
+        }
+        let response = response
+            .error_for_status()
+            .map_err(|_| FetchError::Unavailable)?;
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned);
+        let body = response.bytes().map_err(|_| FetchError::Unavailable)?;
+        let seeds = parse_list(
+            &body,
+            #[cfg(feature = "ed25519")]
+            self.signing_public_key,
+        )
+        .map_err(|_| FetchError::Invalid)?;
+        Ok(FetchOutcome::Fresh { seeds, etag })
+    }
+}
 
+#[cfg(feature = "reqwest")]
+enum FetchOutcome {
+    Fresh { seeds: Vec<Vec<u8>>, etag: Option<String> },
+    NotModified,
+}
+
+/// Distinguishes *why* a list wasn't honored, so `check_block` can apply `fail_open` only to the
+/// "couldn't reach the server" case: a list that was fetched but fails to parse or verify
+/// indicates tampering, not mere unavailability, and must always be treated as blocked.
+#[cfg(feature = "reqwest")]
+enum FetchError {
+    /// The request itself failed: a network error, or a non-2xx / unreadable response.
+    Unavailable,
+    /// A response was received but its body didn't parse as a valid (optionally signed) list,
+    /// or it was a 304 to a request that sent no If-None-Match.
+    Invalid,
+}
+
+/// Parses a blocklist body: one base64-encoded seed per line, optionally followed by a final
+/// line holding a base64-encoded detached Ed25519 signature over the preceding lines.
+#[cfg(feature = "reqwest")]
+pub(crate) fn parse_list(
+    body: &[u8],
+    #[cfg(feature = "ed25519")] signing_public_key: Option<[u8; 32]>,
+) -> Result<Vec<Vec<u8>>, BlockCheckError> {
+    // A single trailing newline is the overwhelmingly common case for a hosted text file; without
+    // trimming it, splitting on b'\n' would treat the resulting empty line as the signature.
+    let body = body.strip_suffix(b"\n").unwrap_or(body);
+    let lines: Vec<&[u8]> = body.split(|x| *x == b'\n').collect();
+    #[cfg(feature = "ed25519")]
+    let lines = if let Some(public_key) = signing_public_key {
+        let (signature_line, body_lines) = lines.split_last().ok_or(BlockCheckError::BadList)?;
+        let signature_bytes = base64engine
+            .decode(signature_line)
+            .map_err(|_| BlockCheckError::BadList)?;
+        let signature_bytes: [u8; 64] = signature_bytes
+            .try_into()
+            .map_err(|_| BlockCheckError::BadList)?;
+        let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&public_key)
+            .map_err(|_| BlockCheckError::BadList)?;
+        let signature = ed25519_dalek::Signature::from_bytes(&signature_bytes);
+        let message = body_lines.join(&b'\n');
+        use ed25519_dalek::Verifier;
+        if verifying_key.verify(&message, &signature).is_err() {
+            return Err(BlockCheckError::BadList);
+        }
+        body_lines.to_vec()
+    } else {
+        lines
+    };
+    let mut seeds = vec![];
+    for line in lines {
+        seeds.push(base64engine.decode(line).map_err(|_| BlockCheckError::BadList)?);
+    }
+    Ok(seeds)
+}
+
+#[cfg(feature = "reqwest")]
 impl Blocker for RemoteFileBlocker {
     fn check_block(&self, seed: &[u8]) -> Result<(), BlockCheckError> {
-        match reqwest::blocking::get(self.url.clone()) {
-            Ok(response) => match response.error_for_status() {
-                Ok(response) => {
-                    if let Ok(body) = response.bytes() {
-                        let seeds_encoded = body.split(|x| *x == b'\n');
-                        let mut seeds = vec![];
-                        for seed in seeds_encoded {
-                            if let Ok(b) = base64engine.decode(seed) {
-                                seeds.push(b);
-                            } else {
-                                return Err(BlockCheckError::BadList);
-                            }
-                        }
-                        if seeds.contains(&seed.to_vec()) {
-                            return Err(BlockCheckError::Blocked);
-                        }
-                    } else {
-                        return Err(BlockCheckError::BadList);
-                    }
+        let mut cache = self.cache.lock().expect("blocklist cache lock poisoned");
+        if let Some(cached) = cache.as_ref() {
+            if cached.fetched_at.elapsed() < self.ttl {
+                return if cached.seeds.contains(&seed.to_vec()) {
+                    Err(BlockCheckError::Blocked)
+                } else {
+                    Ok(())
+                };
+            }
+        }
+        match self.fetch(cache.as_ref().and_then(|c| c.etag.as_deref())) {
+            Ok(FetchOutcome::Fresh { seeds, etag }) => {
+                let blocked = seeds.contains(&seed.to_vec());
+                *cache = Some(CachedList {
+                    seeds,
+                    etag,
+                    fetched_at: Instant::now(),
+                });
+                if blocked {
+                    Err(BlockCheckError::Blocked)
+                } else {
+                    Ok(())
+                }
+            }
+            Ok(FetchOutcome::NotModified) => {
+                let cached = cache.as_mut().expect("304 Not Modified without a cached list");
+                cached.fetched_at = Instant::now();
+                if cached.seeds.contains(&seed.to_vec()) {
+                    Err(BlockCheckError::Blocked)
+                } else {
+                    Ok(())
                 }
-                Err(_) => return Err(BlockCheckError::BadList),
-            },
-            Err(_) => return Err(BlockCheckError::BadList),
+            }
+            // A list that was fetched but failed to parse or verify is never eligible for
+            // fail-open: that outcome means tampering, not unavailability.
+            Err(FetchError::Invalid) => Err(BlockCheckError::BadList),
+            Err(FetchError::Unavailable) => {
+                if self.fail_open {
+                    Ok(())
+                } else {
+                    Err(BlockCheckError::BadList)
+                }
+            }
         }
-        Ok(())
     }
 }
-#[derive(PartialEq, Debug, Error)]
 
+#[derive(PartialEq, Debug, Error)]
 pub enum BlockCheckError {
     #[error("list provided is invalid")]
     BadList,
@@ -1,18 +1,40 @@
 use crate::{
-    blockers, generate_checksum, generate_key_chunk, LicenseStructParameters, CHECKSUM_LEN,
+    blockers, clock::Clock, generate_checksum, generate_key_chunk,
+    header::{HeaderError, LicenseHeader},
+    LicenseStructParameters, Validity, CHECKSUM_LEN, TIMESTAMP_OFFSET,
 };
 use base64::{engine::general_purpose::STANDARD_NO_PAD as base64engine, Engine};
 use thiserror::Error;
+#[cfg(feature = "ed25519")]
+use crate::IntermediateCert;
 /// Information needed for validating a license. If a keygen is made for your software, update this.
-pub struct LicenseCheckInfo {
-    pub known_iv: Vec<u8>,
-    pub iv_index: usize,
+pub enum LicenseCheckInfo {
+    /// Validate by recomputing the key chunk at `iv_index` from a shared secret IV.
+    SharedSecret { known_iv: Vec<u8>, iv_index: usize },
+    /// Validate a license signed by `gen::AdminGenerator::with_ed25519_signing_key` using only
+    /// the signer's public key, so the client never holds anything capable of forging a license.
+    #[cfg(feature = "ed25519")]
+    Ed25519 { public_key: [u8; 32] },
+    /// Validate a license whose leaf signature was made by an intermediate key, itself
+    /// delegated by `root_public_key` via an embedded `IntermediateCert`; see
+    /// `gen::AdminGenerator::issue_intermediate`.
+    #[cfg(feature = "ed25519")]
+    Ed25519Chained { root_public_key: [u8; 32] },
 }
 /// Information contained within the license bytes.
 #[derive(Clone)]
 pub struct License {
     pub seed: Vec<u8>,
     pub payload: Vec<Vec<u8>>,
+    /// Present when the license was generated with `LicenseStructParameters::validity_enabled`.
+    pub validity: Option<Validity>,
+    /// Present when the license was generated with `LicenseStructParameters::ed25519_chain_enabled`.
+    #[cfg(feature = "ed25519")]
+    pub intermediate_cert: Option<IntermediateCert>,
+    /// Present when the license was generated with `LicenseStructParameters::ed25519_enabled` or
+    /// `ed25519_chain_enabled`.
+    #[cfg(feature = "ed25519")]
+    pub signature: Option<[u8; crate::SIGNATURE_LEN]>,
     pub checksum: Vec<u8>,
 }
 
@@ -20,49 +42,194 @@ pub struct License {
 pub enum LicenseParseError {
     #[error("invalid license length")]
     InvalidLength,
+    /// The license bytes' wire header couldn't be parsed (too short, bad magic, or an
+    /// unsupported format version) or names a `checksum_len` this build doesn't use.
+    #[error("invalid license header: {0}")]
+    InvalidHeader(#[from] HeaderError),
+    /// The license bytes' wire header doesn't match the `LicenseStructParameters` the caller
+    /// supplied; use `from_license_bytes_self_describing` if you don't have trusted params to
+    /// check against.
+    #[error("license header doesn't match the supplied parameters")]
+    HeaderMismatch,
 }
-/// Check if a license is valid (checksum and key bytes).
+/// Check if a license is valid (checksum, key bytes or signature, and validity window).
 /// A blocker is used to check if a license seed is blocked, but if you don't want it, set it to blockers::NoBlock.
-pub fn verify_license<T: crate::blockers::Blocker>(
+/// A clock is used to check the license's validity window against the current time; use
+/// `clock::SystemClock` in production.
+pub fn verify_license<T: crate::blockers::Blocker, C: Clock>(
     license: License,
     info: LicenseCheckInfo,
     blocker: T,
+    clock: &C,
 ) -> Result<(), LicenseVerifyError> {
     if license.verify_checksum().is_err() {
         return Err(LicenseVerifyError::ChecksumFailed);
     }
-    let chunk_size = match license.payload.get(info.iv_index) {
-        None => return Err(LicenseVerifyError::InvalidIVIndex),
-        Some(t) => t,
+    match info {
+        LicenseCheckInfo::SharedSecret { known_iv, iv_index } => {
+            let chunk_size = match license.payload.get(iv_index) {
+                None => return Err(LicenseVerifyError::InvalidIVIndex),
+                Some(t) => t,
+            }
+            .len();
+            if license.payload[iv_index] != generate_key_chunk(&known_iv, &license.seed, chunk_size)
+            {
+                return Err(LicenseVerifyError::LicenseForged);
+            }
+        }
+        #[cfg(feature = "ed25519")]
+        LicenseCheckInfo::Ed25519 { public_key } => {
+            let signature = license
+                .signature
+                .ok_or(LicenseVerifyError::MissingSignature)?;
+            let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&public_key)
+                .map_err(|_| LicenseVerifyError::LicenseForged)?;
+            let signature = ed25519_dalek::Signature::from_bytes(&signature);
+            let message = crate::signable_message(&license.seed, &license.payload, license.validity);
+            use ed25519_dalek::Verifier;
+            if verifying_key.verify(&message, &signature).is_err() {
+                return Err(LicenseVerifyError::LicenseForged);
+            }
+        }
+        #[cfg(feature = "ed25519")]
+        LicenseCheckInfo::Ed25519Chained { root_public_key } => {
+            let cert = license
+                .intermediate_cert
+                .ok_or(LicenseVerifyError::MissingSignature)?;
+            let root_key = ed25519_dalek::VerifyingKey::from_bytes(&root_public_key)
+                .map_err(|_| LicenseVerifyError::LicenseForged)?;
+            let cert_message = IntermediateCert::signable_message(&cert.public_key, cert.validity);
+            let cert_signature = ed25519_dalek::Signature::from_bytes(&cert.signature);
+            use ed25519_dalek::Verifier;
+            if root_key.verify(&cert_message, &cert_signature).is_err() {
+                return Err(LicenseVerifyError::LicenseForged);
+            }
+            let leaf_signature = license
+                .signature
+                .ok_or(LicenseVerifyError::MissingSignature)?;
+            let intermediate_key = ed25519_dalek::VerifyingKey::from_bytes(&cert.public_key)
+                .map_err(|_| LicenseVerifyError::LicenseForged)?;
+            let leaf_message =
+                crate::signable_message(&license.seed, &license.payload, license.validity);
+            let leaf_signature = ed25519_dalek::Signature::from_bytes(&leaf_signature);
+            if intermediate_key
+                .verify(&leaf_message, &leaf_signature)
+                .is_err()
+            {
+                return Err(LicenseVerifyError::LicenseForged);
+            }
+            let leaf_validity = license.validity.unwrap_or_default();
+            if !IntermediateCert::contains(cert.validity, leaf_validity) {
+                return Err(LicenseVerifyError::Bounds {
+                    outer_start: cert.validity.start,
+                    outer_end: cert.validity.end,
+                    inner_start: leaf_validity.start,
+                    inner_end: leaf_validity.end,
+                });
+            }
+        }
     }
-    .len();
-    if license.payload[info.iv_index]
-        == generate_key_chunk(&info.known_iv, &license.seed, chunk_size)
-    {
-        if let Err(e) = blocker.check_block(&license.seed) {
-            return Err(LicenseVerifyError::LicenseBlocked(e));
+    if let Some(validity) = license.validity {
+        let now = clock.now();
+        let not_yet_valid = now < TIMESTAMP_OFFSET + validity.start as u64;
+        let expired = validity.end != 0 && now > TIMESTAMP_OFFSET + validity.end as u64;
+        if not_yet_valid || expired {
+            return Err(LicenseVerifyError::Expired {
+                start: validity.start,
+                end: validity.end,
+            });
         }
-        Ok(())
-    } else {
-        Err(LicenseVerifyError::LicenseForged)
     }
+    if let Err(e) = blocker.check_block(&license.seed) {
+        return Err(LicenseVerifyError::LicenseBlocked(e));
+    }
+    Ok(())
 }
 impl License {
     /// Verifies only the checksum of your license, ignoring validity of key bytes.
     pub fn verify_checksum(&self) -> Result<(), WrongChecksum> {
-        let checksum = generate_checksum(&self.seed, &self.payload);
+        let checksum = generate_checksum(&self.seed, &self.payload, self.validity);
         if checksum == self.checksum {
             Ok(())
         } else {
             Err(WrongChecksum)
         }
     }
+    /// Parses license bytes whose leading wire header is cross-checked against `params`,
+    /// erroring on any divergence between what the header says and what you expected. Use this
+    /// when you already know the expected `LicenseStructParameters` and want format drift or
+    /// truncation caught explicitly rather than surfacing as a generic length mismatch.
     pub fn from_license_bytes(
         license_bytes: Vec<u8>,
         params: LicenseStructParameters,
     ) -> Result<License, LicenseParseError> {
+        let header = LicenseHeader::from_bytes(&license_bytes)?;
+        if !header.matches(&params) {
+            return Err(LicenseParseError::HeaderMismatch);
+        }
+        Self::from_body_bytes(
+            &license_bytes[LicenseHeader::ENCODED_LEN..],
+            params,
+            header.checksum_len,
+        )
+    }
+    /// Parses license bytes using only their own embedded wire header, without needing
+    /// `LicenseStructParameters` supplied out-of-band.
+    pub fn from_license_bytes_self_describing(
+        license_bytes: Vec<u8>,
+    ) -> Result<License, LicenseParseError> {
+        let header = LicenseHeader::from_bytes(&license_bytes)?;
+        let params = header.to_params();
+        Self::from_body_bytes(
+            &license_bytes[LicenseHeader::ENCODED_LEN..],
+            params,
+            header.checksum_len,
+        )
+    }
+    /// `checksum_len` is the value recovered from the wire header; it's cross-checked against
+    /// this build's `CHECKSUM_LEN` up front so the rest of the function can rely on it (rather
+    /// than silently trusting the out-of-band constant if the two ever diverge).
+    fn from_body_bytes(
+        license_bytes: &[u8],
+        params: LicenseStructParameters,
+        checksum_len: u32,
+    ) -> Result<License, LicenseParseError> {
+        if checksum_len as usize != CHECKSUM_LEN {
+            return Err(LicenseParseError::InvalidHeader(
+                HeaderError::ChecksumLenMismatch {
+                    header: checksum_len,
+                    crate_const: CHECKSUM_LEN as u32,
+                },
+            ));
+        }
         let payload_len_in_bytes = params.payload_length * params.chunk_size;
-        let should_len = params.seed_length + payload_len_in_bytes + CHECKSUM_LEN;
+        let validity_len = if params.validity_enabled {
+            Validity::ENCODED_LEN
+        } else {
+            0
+        };
+        #[cfg(feature = "ed25519")]
+        let intermediate_cert_len = if params.ed25519_chain_enabled {
+            IntermediateCert::ENCODED_LEN
+        } else {
+            0
+        };
+        #[cfg(not(feature = "ed25519"))]
+        let intermediate_cert_len = 0;
+        #[cfg(feature = "ed25519")]
+        let signature_len = if params.ed25519_enabled || params.ed25519_chain_enabled {
+            crate::SIGNATURE_LEN
+        } else {
+            0
+        };
+        #[cfg(not(feature = "ed25519"))]
+        let signature_len = 0;
+        let should_len = params.seed_length
+            + payload_len_in_bytes
+            + validity_len
+            + intermediate_cert_len
+            + signature_len
+            + checksum_len as usize;
         if license_bytes.len() != should_len {
             return Err(LicenseParseError::InvalidLength);
         }
@@ -75,10 +242,43 @@ impl License {
             chunks.push(og_payload[i..i + params.chunk_size].to_vec());
             i += params.chunk_size
         }
+        let validity_start = params.seed_length + payload_len_in_bytes;
+        let validity = if params.validity_enabled {
+            Some(Validity::from_bytes(
+                &license_bytes[validity_start..validity_start + Validity::ENCODED_LEN],
+            ))
+        } else {
+            None
+        };
+        #[cfg(feature = "ed25519")]
+        let intermediate_cert = if params.ed25519_chain_enabled {
+            let cert_start = validity_start + validity_len;
+            Some(IntermediateCert::from_bytes(
+                &license_bytes[cert_start..cert_start + IntermediateCert::ENCODED_LEN],
+            ))
+        } else {
+            None
+        };
+        #[cfg(feature = "ed25519")]
+        let signature = if params.ed25519_enabled || params.ed25519_chain_enabled {
+            let signature_start = validity_start + validity_len + intermediate_cert_len;
+            let mut sig = [0u8; crate::SIGNATURE_LEN];
+            sig.copy_from_slice(
+                &license_bytes[signature_start..signature_start + crate::SIGNATURE_LEN],
+            );
+            Some(sig)
+        } else {
+            None
+        };
         Ok(License {
             seed: license_bytes[..params.seed_length].to_vec(),
             payload: chunks,
-            checksum: license_bytes[license_bytes.len() - CHECKSUM_LEN..].to_vec(),
+            validity,
+            #[cfg(feature = "ed25519")]
+            intermediate_cert,
+            #[cfg(feature = "ed25519")]
+            signature,
+            checksum: license_bytes[license_bytes.len() - checksum_len as usize..].to_vec(),
         })
     }
     pub fn from_human_readable(
@@ -95,6 +295,21 @@ impl License {
             Err(err) => Err(HumanReadableParseError::ParseBytesError(err)),
         }
     }
+    /// Parses a human-readable license using only its own embedded wire header, without needing
+    /// `LicenseStructParameters` supplied out-of-band.
+    pub fn from_human_readable_self_describing(
+        readable: String,
+    ) -> Result<License, HumanReadableParseError> {
+        let filtered: Vec<u8> = readable.bytes().filter(|x| *x != b'-').collect();
+        let decoded = match base64engine.decode(filtered) {
+            Ok(d) => d,
+            Err(err) => return Err(HumanReadableParseError::Base64DecodeError(err)),
+        };
+        match Self::from_license_bytes_self_describing(decoded) {
+            Ok(p) => Ok(p),
+            Err(err) => Err(HumanReadableParseError::ParseBytesError(err)),
+        }
+    }
 }
 #[derive(Debug)]
 
@@ -115,4 +330,20 @@ pub enum LicenseVerifyError {
     LicenseForged,
     #[error("license has been blocked")]
     LicenseBlocked(blockers::BlockCheckError),
+    #[error("license is outside its validity window ({start}-{end})")]
+    Expired { start: u32, end: u32 },
+    /// The check info requested Ed25519 verification, but the license has no signature (or, for
+    /// chained verification, no intermediate cert) embedded.
+    #[cfg(feature = "ed25519")]
+    #[error("license has no signature to verify")]
+    MissingSignature,
+    /// The leaf license's validity window isn't fully contained within its intermediate cert's.
+    #[cfg(feature = "ed25519")]
+    #[error("license validity window ({inner_start}-{inner_end}) escapes its intermediate cert's ({outer_start}-{outer_end})")]
+    Bounds {
+        outer_start: u32,
+        outer_end: u32,
+        inner_start: u32,
+        inner_end: u32,
+    },
 }
@@ -0,0 +1,28 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A source of the current time, in seconds since the UNIX epoch.
+///
+/// `verify_license` takes one of these instead of calling `SystemTime::now()` directly so
+/// expiry checks can be driven by a fixed time in tests.
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+/// Clock backed by the system wall clock. Use this in production.
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock is set before the unix epoch")
+            .as_secs()
+    }
+}
+
+/// Clock that always reports the same fixed time, for deterministic tests.
+pub struct FixedClock(pub u64);
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
@@ -1,11 +1,25 @@
-use crate::{check::License, generate_checksum, generate_key_chunk, LicenseStructParameters};
+use crate::{
+    check::License, generate_checksum, generate_key_chunk, header::LicenseHeader,
+    seed::SeedBuilder, LicenseStructParameters, Validity,
+};
 use base64::{engine::general_purpose::STANDARD_NO_PAD as base64engine, Engine};
 use rand::{self, rngs::OsRng, Rng, RngCore};
 use thiserror::Error;
+#[cfg(feature = "ed25519")]
+use crate::IntermediateCert;
 /// For a piece of software, the admin generator should be created and stored once
 pub struct AdminGenerator {
     pub parameters: LicenseStructParameters,
     pub ivs: Vec<Vec<u8>>,
+    /// Signs every generated license when set; see `LicenseStructParameters::ed25519_enabled`
+    /// and `check::LicenseCheckInfo::Ed25519`. When `ed25519_chain_enabled` is set instead, this
+    /// is the *intermediate's* key, and `intermediate_cert` must also be set.
+    #[cfg(feature = "ed25519")]
+    pub signing_key: Option<ed25519_dalek::SigningKey>,
+    /// Embedded alongside the leaf signature so a client that only pins the root public key can
+    /// verify the delegation to `signing_key`; see `LicenseStructParameters::ed25519_chain_enabled`.
+    #[cfg(feature = "ed25519")]
+    pub intermediate_cert: Option<IntermediateCert>,
 }
 impl AdminGenerator {
     /// Creates a new admin generator with your parameters using initialization vectors (IVs)
@@ -24,33 +38,176 @@ impl AdminGenerator {
             rng.fill_bytes(&mut iv);
             ivs.push(iv);
         }
-        Self { parameters, ivs }
+        Self {
+            parameters,
+            ivs,
+            #[cfg(feature = "ed25519")]
+            signing_key: None,
+            #[cfg(feature = "ed25519")]
+            intermediate_cert: None,
+        }
+    }
+    /// Signs every license this generator produces with `signing_key`, for the asymmetric
+    /// verification mode. `self.parameters.ed25519_enabled` (or `ed25519_chain_enabled`, if
+    /// `signing_key` is an intermediate's key) must also be set.
+    #[cfg(feature = "ed25519")]
+    pub fn with_ed25519_signing_key(mut self, signing_key: ed25519_dalek::SigningKey) -> Self {
+        self.signing_key = Some(signing_key);
+        self
     }
-    /// Create a new valid license
-    pub fn generate_license(&self, seed: Vec<u8>) -> Result<License, LicenseGenError> {
+    /// Embeds `cert` in every generated license, delegating trust to `self.signing_key` for
+    /// clients that only pin `cert`'s issuer. Requires `self.parameters.ed25519_chain_enabled`.
+    #[cfg(feature = "ed25519")]
+    pub fn with_intermediate_cert(mut self, cert: IntermediateCert) -> Self {
+        self.intermediate_cert = Some(cert);
+        self
+    }
+    /// Delegates license-signing authority to `intermediate_public_key`, scoped to `validity`,
+    /// by signing it with this generator's own (root) `signing_key`. Give the resulting cert to
+    /// `with_intermediate_cert` on a generator holding the intermediate's private key.
+    #[cfg(feature = "ed25519")]
+    pub fn issue_intermediate(
+        &self,
+        intermediate_public_key: [u8; 32],
+        validity: Validity,
+    ) -> Result<IntermediateCert, LicenseGenError> {
+        let signing_key = self
+            .signing_key
+            .as_ref()
+            .ok_or(LicenseGenError::MissingSigningKey)?;
+        use ed25519_dalek::Signer;
+        let message = IntermediateCert::signable_message(&intermediate_public_key, validity);
+        let signature = signing_key.sign(&message).to_bytes();
+        Ok(IntermediateCert {
+            public_key: intermediate_public_key,
+            validity,
+            signature,
+        })
+    }
+    /// Create a new valid license. `validity` is only embedded in the license when
+    /// `self.parameters.validity_enabled` is set; pass `None` to mint a license that's valid
+    /// forever, or `Some(validity)` with `validity.end == 0` for the same effect on a
+    /// validity-enabled generator.
+    pub fn generate_license(
+        &self,
+        seed: Vec<u8>,
+        validity: Option<Validity>,
+    ) -> Result<License, LicenseGenError> {
         if seed.len() != self.parameters.seed_length {
             return Err(LicenseGenError::InvalidSeedLen);
         }
+        #[cfg(feature = "ed25519")]
+        if self.parameters.ed25519_chain_enabled && !self.parameters.validity_enabled {
+            return Err(LicenseGenError::ChainedWithoutValidity);
+        }
         let mut payload = vec![];
         for iv in &self.ivs {
             payload.push(generate_key_chunk(iv, &seed, self.parameters.chunk_size));
         }
-        let checksum = generate_checksum(&seed, &payload);
+        let validity = self
+            .parameters
+            .validity_enabled
+            .then(|| validity.unwrap_or_default());
+        #[cfg(feature = "ed25519")]
+        let intermediate_cert = if self.parameters.ed25519_chain_enabled {
+            Some(
+                self.intermediate_cert
+                    .ok_or(LicenseGenError::MissingIntermediateCert)?,
+            )
+        } else {
+            None
+        };
+        #[cfg(feature = "ed25519")]
+        let signature = if self.parameters.ed25519_enabled || self.parameters.ed25519_chain_enabled
+        {
+            let signing_key = self
+                .signing_key
+                .as_ref()
+                .ok_or(LicenseGenError::MissingSigningKey)?;
+            use ed25519_dalek::Signer;
+            let message = crate::signable_message(&seed, &payload, validity);
+            Some(signing_key.sign(&message).to_bytes())
+        } else {
+            None
+        };
+        let checksum = generate_checksum(&seed, &payload, validity);
         Ok(License {
             seed,
             payload,
+            validity,
+            #[cfg(feature = "ed25519")]
+            intermediate_cert,
+            #[cfg(feature = "ed25519")]
+            signature,
             checksum,
         })
     }
+    /// Like `generate_license`, but packs the seed from a `SeedBuilder` instead of taking raw
+    /// bytes, so the caller doesn't have to pack fields by hand.
+    pub fn generate_license_from_builder(
+        &self,
+        seed: SeedBuilder,
+        validity: Option<Validity>,
+    ) -> Result<License, LicenseGenError> {
+        self.generate_license(seed.build(self.parameters.seed_length)?, validity)
+    }
 }
 #[derive(Debug, Error)]
 pub enum LicenseGenError {
     #[error("seed length is invalid")]
     InvalidSeedLen,
+    /// The `SeedBuilder` couldn't pack a seed of `self.parameters.seed_length` bytes: its
+    /// fields don't fit, or its `LicenseType` wouldn't round-trip (see `seed::SeedError`).
+    #[error("seed builder couldn't pack a valid seed: {0}")]
+    InvalidSeed(#[from] crate::seed::SeedError),
+    /// `ed25519_enabled` or `ed25519_chain_enabled` is set but no signing key was given to the generator.
+    #[cfg(feature = "ed25519")]
+    #[error("ed25519 signing is enabled but no signing key was provided")]
+    MissingSigningKey,
+    /// `LicenseStructParameters::ed25519_chain_enabled` is set but no intermediate cert was given to the generator.
+    #[cfg(feature = "ed25519")]
+    #[error("ed25519 chained signing is enabled but no intermediate cert was provided")]
+    MissingIntermediateCert,
+    /// `ed25519_chain_enabled` is set without `validity_enabled`. A chained license with no
+    /// validity window is checked as `validity.unwrap_or_default()` (start 0, never expires):
+    /// that fails the intermediate cert's bounds check unless the cert itself starts at 0 and
+    /// never expires, in which case it passes with the expiry check skipped entirely.
+    #[cfg(feature = "ed25519")]
+    #[error("ed25519_chain_enabled requires validity_enabled, or the chain's time bound is unenforceable")]
+    ChainedWithoutValidity,
 }
 impl License {
+    /// Encodes this license as `header || seed || payload || validity || intermediate_cert ||
+    /// signature || checksum`; the leading header is self-describing, so
+    /// `from_license_bytes_self_describing` can recover the structure on the other end without
+    /// being told it separately.
     pub fn to_bytes(self) -> Vec<u8> {
-        [self.seed, self.payload.concat(), self.checksum].concat()
+        let header_bytes = LicenseHeader::from_license(&self).to_bytes().to_vec();
+        let validity_bytes = self
+            .validity
+            .map(|v| v.to_bytes().to_vec())
+            .unwrap_or_default();
+        #[cfg(feature = "ed25519")]
+        let intermediate_cert_bytes = self
+            .intermediate_cert
+            .map(|c| c.to_bytes().to_vec())
+            .unwrap_or_default();
+        #[cfg(not(feature = "ed25519"))]
+        let intermediate_cert_bytes: Vec<u8> = Vec::new();
+        #[cfg(feature = "ed25519")]
+        let signature_bytes = self.signature.map(|s| s.to_vec()).unwrap_or_default();
+        #[cfg(not(feature = "ed25519"))]
+        let signature_bytes: Vec<u8> = Vec::new();
+        [
+            header_bytes,
+            self.seed,
+            self.payload.concat(),
+            validity_bytes,
+            intermediate_cert_bytes,
+            signature_bytes,
+            self.checksum,
+        ]
+        .concat()
     }
 }
 impl License {
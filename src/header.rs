@@ -0,0 +1,119 @@
+//! Self-describing wire header prepended to license bytes, so a verifier can recover a
+//! license's `LicenseStructParameters` instead of needing them supplied out-of-band.
+use crate::{check::License, LicenseStructParameters};
+use thiserror::Error;
+
+const MAGIC: u8 = 0xEC;
+const FORMAT_VERSION: u8 = 1;
+
+const FLAG_VALIDITY: u8 = 0b0000_0001;
+#[cfg(feature = "ed25519")]
+const FLAG_ED25519: u8 = 0b0000_0010;
+#[cfg(feature = "ed25519")]
+const FLAG_ED25519_CHAIN: u8 = 0b0000_0100;
+
+/// The structure of a license, as recovered from (or about to be written as) a wire header:
+/// `magic || version || seed_length || payload_length || chunk_size || checksum_len || flags`.
+pub struct LicenseHeader {
+    pub seed_length: u32,
+    pub payload_length: u32,
+    pub chunk_size: u32,
+    pub checksum_len: u32,
+    flags: u8,
+}
+
+impl LicenseHeader {
+    pub const ENCODED_LEN: usize = 1 + 1 + 4 + 4 + 4 + 4 + 1;
+
+    pub(crate) fn from_license(license: &License) -> Self {
+        let mut flags = 0u8;
+        if license.validity.is_some() {
+            flags |= FLAG_VALIDITY;
+        }
+        #[cfg(feature = "ed25519")]
+        if license.signature.is_some() && license.intermediate_cert.is_none() {
+            flags |= FLAG_ED25519;
+        }
+        #[cfg(feature = "ed25519")]
+        if license.intermediate_cert.is_some() {
+            flags |= FLAG_ED25519_CHAIN;
+        }
+        Self {
+            seed_length: license.seed.len() as u32,
+            payload_length: license.payload.len() as u32,
+            chunk_size: license.payload.first().map(|c| c.len()).unwrap_or(0) as u32,
+            checksum_len: license.checksum.len() as u32,
+            flags,
+        }
+    }
+
+    pub fn to_params(&self) -> LicenseStructParameters {
+        LicenseStructParameters {
+            seed_length: self.seed_length as usize,
+            payload_length: self.payload_length as usize,
+            chunk_size: self.chunk_size as usize,
+            validity_enabled: self.flags & FLAG_VALIDITY != 0,
+            #[cfg(feature = "ed25519")]
+            ed25519_enabled: self.flags & FLAG_ED25519 != 0,
+            #[cfg(feature = "ed25519")]
+            ed25519_chain_enabled: self.flags & FLAG_ED25519_CHAIN != 0,
+        }
+    }
+
+    /// Like `to_params() == *params`, except `chunk_size` is ignored when `payload_length` is 0:
+    /// a payload-less license (e.g. ed25519-only) always encodes `chunk_size: 0` since there's no
+    /// payload chunk to measure, regardless of what `chunk_size` its generator was configured
+    /// with, so comparing it would reject a correctly-generated license as a format mismatch.
+    pub fn matches(&self, params: &LicenseStructParameters) -> bool {
+        let mut recovered = self.to_params();
+        if recovered.payload_length == 0 {
+            recovered.chunk_size = params.chunk_size;
+        }
+        recovered == *params
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[0] = MAGIC;
+        bytes[1] = FORMAT_VERSION;
+        bytes[2..6].copy_from_slice(&self.seed_length.to_be_bytes());
+        bytes[6..10].copy_from_slice(&self.payload_length.to_be_bytes());
+        bytes[10..14].copy_from_slice(&self.chunk_size.to_be_bytes());
+        bytes[14..18].copy_from_slice(&self.checksum_len.to_be_bytes());
+        bytes[18] = self.flags;
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HeaderError> {
+        if bytes.len() < Self::ENCODED_LEN {
+            return Err(HeaderError::Truncated);
+        }
+        if bytes[0] != MAGIC {
+            return Err(HeaderError::BadMagic);
+        }
+        if bytes[1] != FORMAT_VERSION {
+            return Err(HeaderError::UnsupportedVersion(bytes[1]));
+        }
+        Ok(Self {
+            seed_length: u32::from_be_bytes(bytes[2..6].try_into().unwrap()),
+            payload_length: u32::from_be_bytes(bytes[6..10].try_into().unwrap()),
+            chunk_size: u32::from_be_bytes(bytes[10..14].try_into().unwrap()),
+            checksum_len: u32::from_be_bytes(bytes[14..18].try_into().unwrap()),
+            flags: bytes[18],
+        })
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum HeaderError {
+    #[error("license bytes are too short to contain a format header")]
+    Truncated,
+    #[error("license bytes don't start with the expected magic byte")]
+    BadMagic,
+    #[error("unsupported license format version {0}")]
+    UnsupportedVersion(u8),
+    /// The header's `checksum_len` doesn't match what this build of the crate computes
+    /// checksums with, so the remaining bytes can't be reliably split into payload/checksum.
+    #[error("license header's checksum_len ({header}) doesn't match this build's ({crate_const})")]
+    ChecksumLenMismatch { header: u32, crate_const: u32 },
+}
@@ -7,208 +7,85 @@
 //! let generator=AdminGenerator::new_with_random_ivs(LicenseStructParameters::default());
 //! // This generates a license with a seed, which should be constant length unique identifier.
 //! // This example just uses some random bytes as a seed. The seed should be the same length as specified in the LicenseStructParameters
-//! let license=generator.generate_license(vec![5, 100, 42, 69, 3,90]).unwrap();
+//! let license=generator.generate_license(vec![5, 100, 42, 69, 3,90], None).unwrap();
 //! println!("{}",license.to_human_readable()); // BWQq-RQNa-kDp6-mJn8-SSEh-UStw-p9+q-krw1-KDH4-mw
 //! ```
 //! Meanwhile on the client side
 //! ```
 //! use licenses_pro::check::*;
+//! use licenses_pro::clock::SystemClock;
+//! use licenses_pro::gen::AdminGenerator;
 //! use licenses_pro::LicenseStructParameters;
-//! let parsed=License::from_human_readable("BWQq-RQNa-kDp6-mJn8-SSEh-UStw-p9+q-krw1-KDH4-mw".to_string(),LicenseStructParameters::default()).unwrap();
-//! let verify_result=verify_license(parsed,LicenseCheckInfo {
-//!        known_iv: vec![43, 194, 247, 127, 168, 171, 16],
+//!
+//! // The generator (and its IVs) normally only live on the admin side; this doctest builds one
+//! // here so the bytes below are a real license rather than arbitrary ones.
+//! let known_iv = vec![43, 194, 247, 127, 168, 171, 16];
+//! let mut generator = AdminGenerator::new_with_random_ivs(LicenseStructParameters::default());
+//! generator.ivs[0] = known_iv.clone();
+//! let license_bytes = generator
+//!     .generate_license(vec![5, 100, 42, 69, 3, 90], None)
+//!     .unwrap()
+//!     .to_bytes();
+//!
+//! let parsed = License::from_license_bytes(license_bytes, LicenseStructParameters::default()).unwrap();
+//! let verify_result = verify_license(parsed, LicenseCheckInfo::SharedSecret {
+//!        known_iv,
 //!        iv_index: 0,
-//!    },licenses_pro::blockers::NoBlock);
-//! // Go ahead and match this!
+//!    }, licenses_pro::blockers::NoBlock, &SystemClock);
+//! assert!(verify_result.is_ok());
 //! ```
 /// Check licenses generated by the generator
-pub mod check {
+pub mod check;
+/// Block compromised licenses
+pub mod blockers;
+/// Generate valid licenses
+pub mod gen;
+/// Sources of the current time, used so license expiry can be tested deterministically.
+pub mod clock;
+/// Self-describing wire header prepended to license bytes.
+pub mod header;
+/// Structured seeds: build a seed from named fields and decode them back out of a license.
+pub mod seed;
 
-    use crate::{
-        blockers, generate_checksum, generate_key_chunk, LicenseStructParameters, CHECKSUM_LEN,
-    };
-    use base64::{engine::general_purpose::STANDARD_NO_PAD as base64engine, Engine};
-    /// Information needed for validating a license. If a keygen is made for your software, update this.
-    pub struct LicenseCheckInfo {
-        pub known_iv: Vec<u8>,
-        pub iv_index: usize,
-    }
-    /// Information contained within the license bytes.
-    #[derive(Clone)]
-    pub struct License {
-        pub seed: Vec<u8>,
-        pub payload: Vec<Vec<u8>>,
-        pub checksum: Vec<u8>,
-    }
+pub(crate) const CHECKSUM_LEN: usize = 2;
 
-    #[derive(Debug)]
-    pub enum LicenseParseError {
-        InvalidLength,
-    }
-    /// Check if a license is valid (checksum and key bytes).
-    /// A blocker is used to check if a license seed is blocked, but if you don't want it, set it to blockers::NoBlock.
-    pub fn verify_license<T: crate::blockers::Blocker>(
-        license: License,
-        info: LicenseCheckInfo,
-        blocker: T,
-    ) -> LicenseVerifyResult {
-        if license.verify_checksum().is_err() {
-            return LicenseVerifyResult::ChecksumFailed;
-        }
-        let chunk_size = match license.payload.get(info.iv_index) {
-            None => return LicenseVerifyResult::InvalidIVIndex,
-            Some(t) => t,
-        }
-        .len();
-        if license.payload[info.iv_index]
-            == generate_key_chunk(&info.known_iv, &license.seed, chunk_size)
-        {
-            if let Err(e) = blocker.check_block(&license.seed) {
-                return LicenseVerifyResult::LicenseBlocked(e);
-            }
-            LicenseVerifyResult::LicenseGood
-        } else {
-            LicenseVerifyResult::LicenseForged
-        }
-    }
-    impl License {
-        /// Verifies only the checksum of your license, ignoring validity of key bytes.
-        pub fn verify_checksum(&self) -> Result<(), ChecksumVerifyError> {
-            let checksum = generate_checksum(&self.seed, &self.payload);
-            if checksum == self.checksum {
-                Ok(())
-            } else {
-                Err(ChecksumVerifyError::ChecksumDoesntMatch)
-            }
-        }
-        pub fn from_license_bytes(
-            license_bytes: Vec<u8>,
-            params: LicenseStructParameters,
-        ) -> Result<License, LicenseParseError> {
-            let payload_len_in_bytes = params.payload_length * params.chunk_size;
-            let should_len = params.seed_length + payload_len_in_bytes + CHECKSUM_LEN;
-            if license_bytes.len() != should_len {
-                return Err(LicenseParseError::InvalidLength);
-            }
-            let og_payload = license_bytes
-                [params.seed_length..params.seed_length + payload_len_in_bytes]
-                .to_vec();
-            let mut chunks = Vec::new();
-            let mut i = 0;
-            while i < og_payload.len() {
-                chunks.push(og_payload[i..i + params.chunk_size].to_vec());
-                i += params.chunk_size
-            }
-            Ok(License {
-                seed: license_bytes[..params.seed_length].to_vec(),
-                payload: chunks,
-                checksum: license_bytes[license_bytes.len() - CHECKSUM_LEN..].to_vec(),
-            })
-        }
-        pub fn from_human_readable(
-            readable: String,
-            params: LicenseStructParameters,
-        ) -> Result<License, HumanReadableParseError> {
-            let filtered: Vec<u8> = readable.bytes().filter(|x| *x != b'-').collect();
-            let decoded = match base64engine.decode(filtered) {
-                Ok(d) => d,
-                Err(err) => return Err(HumanReadableParseError::Base64DecodeError(err)),
-            };
-            match Self::from_license_bytes(decoded, params) {
-                Ok(p) => Ok(p),
-                Err(err) => Err(HumanReadableParseError::ParseBytesError(err)),
-            }
-        }
-    }
-    #[derive(Debug)]
+/// Base time, in seconds since the UNIX epoch, that a validity window's stored start/end are
+/// added to for their absolute time.
+/// Storing timestamps relative to this offset keeps the on-wire numbers small enough to fit
+/// in 32 bits for a long time to come.
+pub const TIMESTAMP_OFFSET: u64 = 1_700_000_000;
 
-    pub enum HumanReadableParseError {
-        Base64DecodeError(base64::DecodeError),
-        ParseBytesError(LicenseParseError),
-    }
-    #[derive(Debug)]
-    pub enum ChecksumVerifyError {
-        ChecksumDoesntMatch,
-    }
-    #[derive(Debug, PartialEq)]
-    pub enum LicenseVerifyResult {
-        InvalidIVIndex,
-        ChecksumFailed,
-        LicenseGood,
-        LicenseForged,
-        LicenseBlocked(blockers::BlockCheckError),
-    }
+/// A license's validity window, stored as seconds since [`TIMESTAMP_OFFSET`].
+/// `end == 0` means the license never expires.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Validity {
+    pub start: u32,
+    pub end: u32,
 }
-/// Block compromised licenses
-pub mod blockers {
-    pub trait Blocker {
-        fn check_block(&self, seed: &[u8]) -> Result<(), BlockCheckError>;
-    }
-    /// Blocker that always returns an Ok result
-    pub struct NoBlock;
-    impl Blocker for NoBlock {
-        fn check_block(&self, _seed: &[u8]) -> Result<(), BlockCheckError> {
-            Ok(())
-        }
-    }
-    /// Blocks seeds hardcoded into the binary
-    pub struct BuiltinBlocklist(Vec<Vec<u8>>);
-    impl Blocker for BuiltinBlocklist {
-        fn check_block(&self, seed: &[u8]) -> Result<(), BlockCheckError> {
-            if self.0.contains(&seed.to_vec()) {
-                Err(BlockCheckError::Blocked)
-            } else {
-                Ok(())
-            }
-        }
-    }
-    /// Fetch a remote page with a blocked base64-encoded seed on each line.
-    /// This is nice because you don't actually have to host a server that validates licenses, you can just host this on pastebin or something.
-    pub struct RemoteFileBlocker {
-        pub url: reqwest::Url,
-    }
 
-    use base64::{engine::general_purpose::STANDARD_NO_PAD as base64engine, Engine};
-    impl Blocker for RemoteFileBlocker {
-        fn check_block(&self, seed: &[u8]) -> Result<(), BlockCheckError> {
-            match reqwest::blocking::get(self.url.clone()) {
-                Ok(response) => match response.error_for_status() {
-                    Ok(response) => {
-                        if let Ok(body) = response.bytes() {
-                            let seeds_encoded = body.split(|x| *x == b'\n');
-                            let mut seeds = vec![];
-                            for seed in seeds_encoded {
-                                if let Ok(b) = base64engine.decode(seed) {
-                                    seeds.push(b);
-                                } else {
-                                    return Err(BlockCheckError::BadList);
-                                }
-                            }
-                            if seeds.contains(&seed.to_vec()) {
-                                return Err(BlockCheckError::Blocked);
-                            }
-                        } else {
-                            return Err(BlockCheckError::BadList);
-                        }
-                    }
-                    Err(_) => return Err(BlockCheckError::BadList),
-                },
-                Err(_) => return Err(BlockCheckError::BadList),
-            }
-            Ok(())
-        }
+impl Validity {
+    pub(crate) const ENCODED_LEN: usize = 8;
+
+    pub(crate) fn to_bytes(self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[..4].copy_from_slice(&self.start.to_be_bytes());
+        bytes[4..].copy_from_slice(&self.end.to_be_bytes());
+        bytes
     }
-    #[derive(PartialEq, Debug)]
 
-    pub enum BlockCheckError {
-        BadList,
-        Blocked,
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            start: u32::from_be_bytes(bytes[..4].try_into().unwrap()),
+            end: u32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+        }
     }
 }
-const CHECKSUM_LEN: usize = 2;
 
 /// Information about the structure of your license.
-/// This must be shared between your generator and checker.
+/// This must be shared between your generator and checker, unless you use
+/// `check::License::from_license_bytes_self_describing`, which recovers it from the license's
+/// own wire header instead.
+#[derive(Debug, Clone, PartialEq)]
 pub struct LicenseStructParameters {
     // seed length in bytes
     pub seed_length: usize,
@@ -217,6 +94,15 @@ pub struct LicenseStructParameters {
     pub payload_length: usize,
     // chunk size in bytes
     pub chunk_size: usize,
+    // whether licenses carry an embedded validity window (see `Validity`)
+    pub validity_enabled: bool,
+    /// Whether licenses carry a trailing Ed25519 signature (see `check::LicenseCheckInfo::Ed25519`).
+    #[cfg(feature = "ed25519")]
+    pub ed25519_enabled: bool,
+    /// Whether licenses carry a leaf signature plus an `IntermediateCert` delegating from a root
+    /// key (see `check::LicenseCheckInfo::Ed25519Chained`). Mutually exclusive with `ed25519_enabled`.
+    #[cfg(feature = "ed25519")]
+    pub ed25519_chain_enabled: bool,
 }
 
 impl Default for LicenseStructParameters {
@@ -225,102 +111,129 @@ impl Default for LicenseStructParameters {
             seed_length: 6,
             payload_length: 10,
             chunk_size: 2,
+            validity_enabled: false,
+            #[cfg(feature = "ed25519")]
+            ed25519_enabled: false,
+            #[cfg(feature = "ed25519")]
+            ed25519_chain_enabled: false,
         }
     }
 }
-fn generate_checksum(seed: &[u8], payload: &[Vec<u8>]) -> Vec<u8> {
-    let mut context = digest::Context::new(&digest::SHA256);
-    let to_verify = &[seed, &payload.concat()].concat();
-    context.update(to_verify);
-    context.finish().as_ref()[..CHECKSUM_LEN].to_owned()
+
+/// Length in bytes of a detached Ed25519 signature.
+#[cfg(feature = "ed25519")]
+pub(crate) const SIGNATURE_LEN: usize = 64;
+
+/// The bytes an Ed25519 signature is computed over: `seed || payload || validity`.
+#[cfg(feature = "ed25519")]
+pub(crate) fn signable_message(seed: &[u8], payload: &[Vec<u8>], validity: Option<Validity>) -> Vec<u8> {
+    let mut message = seed.to_vec();
+    message.extend(payload.concat());
+    if let Some(validity) = validity {
+        message.extend(validity.to_bytes());
+    }
+    message
 }
-use ring::digest::{self, Context, SHA256};
-fn generate_key_chunk(iv: &[u8], seed: &Vec<u8>, chunk_size: usize) -> Vec<u8> {
-    let mut context = Context::new(&SHA256);
-    context.update(&[iv, &seed].concat());
-    let binding = context.finish();
-    let hash = &binding.as_ref()[..chunk_size];
-    hash.to_owned()
+
+/// A certificate delegating license-signing authority from a root key to an intermediate key,
+/// scoped to `validity`. Signed by the root's private key over `public_key || validity bytes`,
+/// so a client that only pins the root public key can verify the delegation before trusting a
+/// leaf license signed by the intermediate.
+#[cfg(feature = "ed25519")]
+#[derive(Debug, Clone, Copy)]
+pub struct IntermediateCert {
+    pub public_key: [u8; 32],
+    pub validity: Validity,
+    pub signature: [u8; SIGNATURE_LEN],
 }
-/// Generate valid licenses
-pub mod gen {
-    use crate::{check::License, generate_checksum, generate_key_chunk, LicenseStructParameters};
-    use base64::{engine::general_purpose::STANDARD_NO_PAD as base64engine, Engine};
-    use rand::{self, rngs::OsRng, Rng, RngCore};
-    /// For a piece of software, the admin generator should be created and stored once
-    pub struct AdminGenerator {
-        pub parameters: LicenseStructParameters,
-        pub ivs: Vec<Vec<u8>>,
-    }
-    impl AdminGenerator {
-        /// Creates a new admin generator with your parameters using initialization vectors (IVs)
-        pub fn new_with_random_ivs(parameters: LicenseStructParameters) -> Self {
-            let mut ivs = vec![];
-            for _ in 0..parameters.payload_length {
-                let mut rng = OsRng;
-                // Arbitrary range
-                let rng_len = rng.gen_range(1..10);
-                let mut iv = vec![];
-                for _ in 0..rng_len {
-                    let mut single = [0u8; 1];
-                    rng.fill_bytes(&mut single);
-                    iv.push(single[0]);
-                }
-                rng.fill_bytes(&mut iv);
-                ivs.push(iv);
-            }
-            Self { parameters, ivs }
-        }
-        /// Create a new valid license
-        pub fn generate_license(&self, seed: Vec<u8>) -> Result<License, LicenseGenError> {
-            if seed.len() != self.parameters.seed_length {
-                return Err(LicenseGenError::InvalidSeedLen);
-            }
-            let mut payload = vec![];
-            for iv in &self.ivs {
-                payload.push(generate_key_chunk(iv, &seed, self.parameters.chunk_size));
-            }
-            let checksum = generate_checksum(&seed, &payload);
-            Ok(License {
-                seed,
-                payload,
-                checksum,
-            })
-        }
+
+#[cfg(feature = "ed25519")]
+impl IntermediateCert {
+    pub(crate) const ENCODED_LEN: usize = 32 + Validity::ENCODED_LEN + SIGNATURE_LEN;
+
+    pub(crate) fn signable_message(public_key: &[u8; 32], validity: Validity) -> Vec<u8> {
+        let mut message = public_key.to_vec();
+        message.extend(validity.to_bytes());
+        message
     }
-    #[derive(Debug)]
-    pub enum LicenseGenError {
-        InvalidSeedLen,
+
+    pub(crate) fn to_bytes(self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[..32].copy_from_slice(&self.public_key);
+        bytes[32..32 + Validity::ENCODED_LEN].copy_from_slice(&self.validity.to_bytes());
+        bytes[32 + Validity::ENCODED_LEN..].copy_from_slice(&self.signature);
+        bytes
     }
-    impl License {
-        pub fn to_bytes(self) -> Vec<u8> {
-            [self.seed, self.payload.concat(), self.checksum].concat()
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        let mut public_key = [0u8; 32];
+        public_key.copy_from_slice(&bytes[..32]);
+        let validity = Validity::from_bytes(&bytes[32..32 + Validity::ENCODED_LEN]);
+        let mut signature = [0u8; SIGNATURE_LEN];
+        signature.copy_from_slice(&bytes[32 + Validity::ENCODED_LEN..]);
+        Self {
+            public_key,
+            validity,
+            signature,
         }
     }
-    impl License {
-        /// Base64 encode your license and seperate it with dashes
-        pub fn to_human_readable(self) -> String {
-            let binding = base64engine.encode(self.to_bytes());
-            let encoded = binding.bytes();
-            let mut dashed = "".to_string();
-            for (i, character) in encoded.enumerate() {
-                if i % 4 == 0 && i != 0 {
-                    dashed += "-"
-                }
-                dashed.push(character.into());
-            }
-            dashed
-        }
+
+    /// Whether `inner` is fully contained within `outer` (an `[start, end]` window, where
+    /// `end == 0` means unbounded).
+    pub(crate) fn contains(outer: Validity, inner: Validity) -> bool {
+        let outer_end = if outer.end == 0 {
+            u64::MAX
+        } else {
+            outer.end as u64
+        };
+        let inner_end = if inner.end == 0 {
+            u64::MAX
+        } else {
+            inner.end as u64
+        };
+        inner.start as u64 >= outer.start as u64 && inner_end <= outer_end
     }
 }
+fn generate_checksum(seed: &[u8], payload: &[Vec<u8>], validity: Option<Validity>) -> Vec<u8> {
+    let mut context = digest::Context::new(&digest::SHA256);
+    context.update(seed);
+    context.update(&payload.concat());
+    if let Some(validity) = validity {
+        context.update(&validity.to_bytes());
+    }
+    context.finish().as_ref()[..CHECKSUM_LEN].to_owned()
+}
+use ring::digest;
+fn generate_key_chunk(iv: &[u8], seed: &[u8], chunk_size: usize) -> Vec<u8> {
+    let mut context = digest::Context::new(&digest::SHA256);
+    context.update(&[iv, seed].concat());
+    let binding = context.finish();
+    let hash = &binding.as_ref()[..chunk_size];
+    hash.to_owned()
+}
 #[cfg(test)]
 mod tests {
-    use crate::check::{LicenseCheckInfo, LicenseVerifyResult};
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::check::LicenseVerifyError;
 
     use self::{
         blockers::NoBlock,
         check::{verify_license, License},
         gen::AdminGenerator,
+        seed::{LicenseType, SeedBuilder},
+    };
+    #[cfg(feature = "reqwest")]
+    use self::blockers::{BlockCheckError, Blocker as _, RemoteFileBlocker};
+    #[cfg(feature = "reqwest")]
+    use base64::{engine::general_purpose::STANDARD_NO_PAD as base64engine, Engine};
+    #[cfg(feature = "reqwest")]
+    use std::{
+        io::{Read, Write},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
     };
 
     use super::*;
@@ -332,7 +245,7 @@ mod tests {
     fn checksum_detects_invalid() {
         let mut license = new_test_license();
         license.payload[0][0] += 1;
-        if let Ok(_) = license.verify_checksum() {
+        if license.verify_checksum().is_ok() {
             panic!("Checksum should not be valid")
         }
     }
@@ -340,53 +253,591 @@ mod tests {
     fn license_works() {
         let genner = new_test_genner();
         let license = genner
-            .generate_license(vec![5, 100, 42, 69, 3, 90])
+            .generate_license(vec![5, 100, 42, 69, 3, 90], None)
             .unwrap();
         println!("{}", license.clone().to_human_readable());
         println!("{:?}", genner.ivs[0]);
         assert_eq!(
             verify_license(
                 license,
-                LicenseCheckInfo {
+                check::LicenseCheckInfo::SharedSecret {
                     known_iv: genner.ivs[0].clone(),
                     iv_index: 0
                 },
-                NoBlock
+                NoBlock,
+                &SystemClock,
             ),
-            LicenseVerifyResult::LicenseGood
+            Ok(())
         );
     }
     #[test]
     fn forgery_detected() {
         let genner = new_test_genner();
         let license = genner
-            .generate_license(vec![5, 100, 42, 69, 3, 90])
+            .generate_license(vec![5, 100, 42, 69, 3, 90], None)
             .unwrap();
-        if let LicenseVerifyResult::LicenseForged = verify_license(
-            license,
-            LicenseCheckInfo {
-                known_iv: vec![182, 34],
-                iv_index: 0,
-            },
-            NoBlock,
-        ) {
-        } else {
-            panic!("Bad license detected as good")
-        }
+        assert_eq!(
+            verify_license(
+                license,
+                check::LicenseCheckInfo::SharedSecret {
+                    known_iv: vec![182, 34],
+                    iv_index: 0,
+                },
+                NoBlock,
+                &SystemClock,
+            ),
+            Err(LicenseVerifyError::LicenseForged)
+        );
+    }
+    #[test]
+    fn expired_license_rejected() {
+        let genner = new_test_genner_with_validity();
+        let license = genner
+            .generate_license(
+                vec![5, 100, 42, 69, 3, 90],
+                Some(Validity {
+                    start: 0,
+                    end: 100,
+                }),
+            )
+            .unwrap();
+        assert_eq!(
+            verify_license(
+                license,
+                check::LicenseCheckInfo::SharedSecret {
+                    known_iv: genner.ivs[0].clone(),
+                    iv_index: 0
+                },
+                NoBlock,
+                &FixedClock(TIMESTAMP_OFFSET + 200),
+            ),
+            Err(LicenseVerifyError::Expired { start: 0, end: 100 })
+        );
+    }
+    #[test]
+    fn never_expiring_license_accepted() {
+        let genner = new_test_genner_with_validity();
+        let license = genner
+            .generate_license(
+                vec![5, 100, 42, 69, 3, 90],
+                Some(Validity { start: 0, end: 0 }),
+            )
+            .unwrap();
+        assert_eq!(
+            verify_license(
+                license,
+                check::LicenseCheckInfo::SharedSecret {
+                    known_iv: genner.ivs[0].clone(),
+                    iv_index: 0
+                },
+                NoBlock,
+                &FixedClock(TIMESTAMP_OFFSET + 1_000_000),
+            ),
+            Ok(())
+        );
+    }
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn ed25519_signed_license_verifies_with_public_key_only() {
+        use ed25519_dalek::SigningKey;
+        let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let params = LicenseStructParameters {
+            seed_length: 6,
+            payload_length: 0,
+            chunk_size: 2,
+            validity_enabled: false,
+            ed25519_enabled: true,
+            ed25519_chain_enabled: false,
+        };
+        let genner =
+            AdminGenerator::new_with_random_ivs(params).with_ed25519_signing_key(signing_key.clone());
+        let license = genner
+            .generate_license(vec![5, 100, 42, 69, 3, 90], None)
+            .unwrap();
+        assert_eq!(
+            verify_license(
+                license,
+                check::LicenseCheckInfo::Ed25519 {
+                    public_key: signing_key.verifying_key().to_bytes(),
+                },
+                NoBlock,
+                &SystemClock,
+            ),
+            Ok(())
+        );
+    }
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn ed25519_signature_mismatch_rejected() {
+        use ed25519_dalek::SigningKey;
+        let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let other_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let params = LicenseStructParameters {
+            seed_length: 6,
+            payload_length: 0,
+            chunk_size: 2,
+            validity_enabled: false,
+            ed25519_enabled: true,
+            ed25519_chain_enabled: false,
+        };
+        let genner = AdminGenerator::new_with_random_ivs(params).with_ed25519_signing_key(signing_key);
+        let license = genner
+            .generate_license(vec![5, 100, 42, 69, 3, 90], None)
+            .unwrap();
+        assert_eq!(
+            verify_license(
+                license,
+                check::LicenseCheckInfo::Ed25519 {
+                    public_key: other_key.verifying_key().to_bytes(),
+                },
+                NoBlock,
+                &SystemClock,
+            ),
+            Err(LicenseVerifyError::LicenseForged)
+        );
+    }
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn chained_license_verifies_against_root_key_only() {
+        use ed25519_dalek::SigningKey;
+        let root_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let intermediate_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let root_genner = AdminGenerator::new_with_random_ivs(LicenseStructParameters {
+            seed_length: 6,
+            payload_length: 0,
+            chunk_size: 2,
+            validity_enabled: false,
+            ed25519_enabled: true,
+            ed25519_chain_enabled: false,
+        })
+        .with_ed25519_signing_key(root_key.clone());
+        let cert = root_genner
+            .issue_intermediate(
+                intermediate_key.verifying_key().to_bytes(),
+                Validity {
+                    start: 0,
+                    end: 1_000,
+                },
+            )
+            .unwrap();
+        let leaf_genner = AdminGenerator::new_with_random_ivs(LicenseStructParameters {
+            seed_length: 6,
+            payload_length: 0,
+            chunk_size: 2,
+            validity_enabled: true,
+            ed25519_enabled: false,
+            ed25519_chain_enabled: true,
+        })
+        .with_ed25519_signing_key(intermediate_key)
+        .with_intermediate_cert(cert);
+        let license = leaf_genner
+            .generate_license(
+                vec![5, 100, 42, 69, 3, 90],
+                Some(Validity { start: 0, end: 500 }),
+            )
+            .unwrap();
+        assert_eq!(
+            verify_license(
+                license,
+                check::LicenseCheckInfo::Ed25519Chained {
+                    root_public_key: root_key.verifying_key().to_bytes(),
+                },
+                NoBlock,
+                &FixedClock(TIMESTAMP_OFFSET + 10),
+            ),
+            Ok(())
+        );
+    }
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn chained_license_outside_intermediate_bounds_rejected() {
+        use ed25519_dalek::SigningKey;
+        let root_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let intermediate_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let root_genner = AdminGenerator::new_with_random_ivs(LicenseStructParameters {
+            seed_length: 6,
+            payload_length: 0,
+            chunk_size: 2,
+            validity_enabled: false,
+            ed25519_enabled: true,
+            ed25519_chain_enabled: false,
+        })
+        .with_ed25519_signing_key(root_key.clone());
+        let cert = root_genner
+            .issue_intermediate(
+                intermediate_key.verifying_key().to_bytes(),
+                Validity {
+                    start: 0,
+                    end: 1_000,
+                },
+            )
+            .unwrap();
+        let leaf_genner = AdminGenerator::new_with_random_ivs(LicenseStructParameters {
+            seed_length: 6,
+            payload_length: 0,
+            chunk_size: 2,
+            validity_enabled: true,
+            ed25519_enabled: false,
+            ed25519_chain_enabled: true,
+        })
+        .with_ed25519_signing_key(intermediate_key)
+        .with_intermediate_cert(cert);
+        let license = leaf_genner
+            .generate_license(
+                vec![5, 100, 42, 69, 3, 90],
+                Some(Validity {
+                    start: 0,
+                    end: 2_000,
+                }),
+            )
+            .unwrap();
+        assert_eq!(
+            verify_license(
+                license,
+                check::LicenseCheckInfo::Ed25519Chained {
+                    root_public_key: root_key.verifying_key().to_bytes(),
+                },
+                NoBlock,
+                &FixedClock(TIMESTAMP_OFFSET + 10),
+            ),
+            Err(LicenseVerifyError::Bounds {
+                outer_start: 0,
+                outer_end: 1_000,
+                inner_start: 0,
+                inner_end: 2_000,
+            })
+        );
+    }
+    #[test]
+    #[cfg(feature = "ed25519")]
+    fn chain_enabled_without_validity_enabled_is_rejected() {
+        // Without validity_enabled, a chained license is checked as "never expires", which
+        // escapes any intermediate cert with a finite end and skips the expiry check entirely.
+        use ed25519_dalek::SigningKey;
+        let intermediate_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let genner = AdminGenerator::new_with_random_ivs(LicenseStructParameters {
+            seed_length: 6,
+            payload_length: 0,
+            chunk_size: 2,
+            validity_enabled: false,
+            ed25519_enabled: false,
+            ed25519_chain_enabled: true,
+        })
+        .with_ed25519_signing_key(intermediate_key);
+        assert!(matches!(
+            genner.generate_license(vec![5, 100, 42, 69, 3, 90], None),
+            Err(gen::LicenseGenError::ChainedWithoutValidity)
+        ));
+    }
+    #[test]
+    fn license_round_trips_through_self_describing_bytes() {
+        let license = new_test_license();
+        let bytes = license.clone().to_bytes();
+        let parsed = License::from_license_bytes_self_describing(bytes).unwrap();
+        assert_eq!(parsed.seed, license.seed);
+        assert_eq!(parsed.payload, license.payload);
+        assert_eq!(parsed.checksum, license.checksum);
+    }
+    #[test]
+    fn self_describing_parse_rejects_bad_magic() {
+        let mut bytes = new_test_license().to_bytes();
+        bytes[0] = !bytes[0];
+        assert!(matches!(
+            License::from_license_bytes_self_describing(bytes),
+            Err(check::LicenseParseError::InvalidHeader(
+                header::HeaderError::BadMagic
+            ))
+        ));
+    }
+    #[test]
+    fn self_describing_parse_rejects_unsupported_version() {
+        let mut bytes = new_test_license().to_bytes();
+        bytes[1] = 250;
+        assert!(matches!(
+            License::from_license_bytes_self_describing(bytes),
+            Err(check::LicenseParseError::InvalidHeader(
+                header::HeaderError::UnsupportedVersion(250)
+            ))
+        ));
+    }
+    #[test]
+    fn strict_parse_rejects_params_diverging_from_header() {
+        let bytes = new_test_license().to_bytes();
+        let wrong_params = LicenseStructParameters {
+            seed_length: 6,
+            payload_length: 10,
+            chunk_size: 3,
+            validity_enabled: false,
+            #[cfg(feature = "ed25519")]
+            ed25519_enabled: false,
+            #[cfg(feature = "ed25519")]
+            ed25519_chain_enabled: false,
+        };
+        assert!(matches!(
+            License::from_license_bytes(bytes, wrong_params),
+            Err(check::LicenseParseError::HeaderMismatch)
+        ));
+    }
+    #[test]
+    #[cfg(feature = "ed25519")]
+    fn strict_parse_accepts_a_payload_less_license_with_its_own_generation_chunk_size() {
+        // A payload_length: 0 license always encodes chunk_size: 0 in its header (there's no
+        // payload chunk to measure), so re-parsing with the exact params it was generated with —
+        // including a nonzero configured chunk_size — must not be rejected as a mismatch.
+        use ed25519_dalek::SigningKey;
+        let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let params = LicenseStructParameters {
+            seed_length: 6,
+            payload_length: 0,
+            chunk_size: 2,
+            validity_enabled: false,
+            ed25519_enabled: true,
+            ed25519_chain_enabled: false,
+        };
+        let genner =
+            AdminGenerator::new_with_random_ivs(params.clone()).with_ed25519_signing_key(signing_key);
+        let bytes = genner
+            .generate_license(vec![5, 100, 42, 69, 3, 90], None)
+            .unwrap()
+            .to_bytes();
+        assert!(License::from_license_bytes(bytes, params).is_ok());
+    }
+    #[test]
+    fn structured_seed_round_trips_through_a_verified_license() {
+        let genner = new_test_genner_with_seed_length(9);
+        let license = genner
+            .generate_license_from_builder(
+                SeedBuilder::new(LicenseType::Pro, 123_456_789),
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            verify_license(
+                license.clone(),
+                check::LicenseCheckInfo::SharedSecret {
+                    known_iv: genner.ivs[0].clone(),
+                    iv_index: 0
+                },
+                NoBlock,
+                &SystemClock,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            license.decoded_seed(),
+            Ok(seed::DecodedSeed {
+                license_type: LicenseType::Pro,
+                customer_id: 123_456_789,
+            })
+        );
+    }
+    #[test]
+    fn seed_builder_rejects_seed_length_too_short_to_hold_it() {
+        let builder = SeedBuilder::new(LicenseType::Trial, 1);
+        assert_eq!(
+            builder.build(6),
+            Err(seed::SeedError::TooShort {
+                required: SeedBuilder::ENCODED_LEN,
+                actual: 6,
+            })
+        );
+    }
+    #[test]
+    fn seed_builder_rejects_a_custom_type_colliding_with_a_named_variant() {
+        let builder = SeedBuilder::new(LicenseType::Custom(2), 1);
+        assert_eq!(
+            builder.build(SeedBuilder::ENCODED_LEN),
+            Err(seed::SeedError::AmbiguousCustomType(2))
+        );
+    }
+    #[test]
+    #[cfg(feature = "reqwest")]
+    fn parse_list_accepts_an_unsigned_list() {
+        let seed = vec![1, 2, 3];
+        let body = format!("{}\n", base64engine.encode(&seed));
+        let seeds = blockers::parse_list(
+            body.as_bytes(),
+            #[cfg(feature = "ed25519")]
+            None,
+        )
+        .unwrap();
+        assert!(seeds.contains(&seed));
+    }
+    #[test]
+    #[cfg(all(feature = "reqwest", feature = "ed25519"))]
+    fn parse_list_accepts_a_signed_list_with_a_trailing_newline() {
+        use ed25519_dalek::{Signer, SigningKey};
+        let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let seed = vec![1, 2, 3];
+        let seed_line = base64engine.encode(&seed);
+        let signature = signing_key.sign(seed_line.as_bytes()).to_bytes();
+        // A trailing newline after the signature line is the common case for a hosted text file.
+        let body = format!("{seed_line}\n{}\n", base64engine.encode(signature));
+        let seeds = blockers::parse_list(body.as_bytes(), Some(signing_key.verifying_key().to_bytes()))
+            .unwrap();
+        assert!(seeds.contains(&seed));
+    }
+    #[test]
+    #[cfg(all(feature = "reqwest", feature = "ed25519"))]
+    fn parse_list_rejects_a_tampered_signature() {
+        use ed25519_dalek::{Signer, SigningKey};
+        let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let seed_line = base64engine.encode([1, 2, 3]);
+        let mut signature = signing_key.sign(seed_line.as_bytes()).to_bytes();
+        signature[0] ^= 0xFF;
+        let body = format!("{seed_line}\n{}\n", base64engine.encode(signature));
+        assert_eq!(
+            blockers::parse_list(body.as_bytes(), Some(signing_key.verifying_key().to_bytes())),
+            Err(BlockCheckError::BadList)
+        );
+    }
+    #[test]
+    #[cfg(feature = "reqwest")]
+    fn check_block_caches_until_ttl_expires() {
+        let seed = vec![1, 2, 3];
+        let response = http_ok_response(&format!("{}\n", base64engine.encode(&seed)), None);
+        let (url, hits) = spawn_test_server(vec![response.clone(), response]);
+        let blocker = RemoteFileBlocker::new(url).with_ttl(Duration::from_millis(500));
+        assert_eq!(blocker.check_block(&seed), Err(BlockCheckError::Blocked));
+        assert_eq!(blocker.check_block(&seed), Err(BlockCheckError::Blocked));
+        assert_eq!(hits.load(Ordering::SeqCst), 1, "second check should be served from cache");
+    }
+    #[test]
+    #[cfg(feature = "reqwest")]
+    fn check_block_revalidates_with_etag_on_ttl_expiry() {
+        let seed = vec![1, 2, 3];
+        let fresh = http_ok_response(&format!("{}\n", base64engine.encode(&seed)), Some("\"v1\""));
+        let not_modified = "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string();
+        let (url, hits) = spawn_test_server(vec![fresh, not_modified]);
+        let blocker = RemoteFileBlocker::new(url);
+        assert_eq!(blocker.check_block(&seed), Err(BlockCheckError::Blocked));
+        assert_eq!(blocker.check_block(&seed), Err(BlockCheckError::Blocked));
+        assert_eq!(hits.load(Ordering::SeqCst), 2, "ttl is 0, so both calls should hit the network");
+    }
+    #[test]
+    #[cfg(feature = "reqwest")]
+    fn check_block_fails_open_on_network_error_when_configured() {
+        let url = dead_server_url();
+        let blocker = RemoteFileBlocker::new(url).with_fail_open(true);
+        assert_eq!(blocker.check_block(&[1, 2, 3]), Ok(()));
+    }
+    #[test]
+    #[cfg(feature = "reqwest")]
+    fn check_block_fails_closed_on_network_error_by_default() {
+        let url = dead_server_url();
+        let blocker = RemoteFileBlocker::new(url);
+        assert_eq!(blocker.check_block(&[1, 2, 3]), Err(BlockCheckError::BadList));
+    }
+    #[test]
+    #[cfg(all(feature = "reqwest", feature = "ed25519"))]
+    fn check_block_never_fails_open_on_a_tampered_signed_list() {
+        use ed25519_dalek::SigningKey;
+        let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        // A body with no trailing signature line at all is an invalid signed list.
+        let body = format!("{}\n", base64engine.encode([1, 2, 3]));
+        let response = http_ok_response(&body, None);
+        let (url, _hits) = spawn_test_server(vec![response]);
+        let blocker = RemoteFileBlocker::new(url)
+            .with_signing_public_key(signing_key.verifying_key().to_bytes())
+            .with_fail_open(true);
+        assert_eq!(blocker.check_block(&[1, 2, 3]), Err(BlockCheckError::BadList));
+    }
+    #[test]
+    #[cfg(feature = "reqwest")]
+    fn check_block_rejects_an_unsolicited_304_on_the_first_fetch() {
+        // No prior cache means no If-None-Match was ever sent, so a 304 here is the host
+        // misbehaving, not a legitimate "nothing changed" reply; it must not panic or pass.
+        let not_modified = "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string();
+        let (url, _hits) = spawn_test_server(vec![not_modified]);
+        let blocker = RemoteFileBlocker::new(url).with_fail_open(true);
+        assert_eq!(blocker.check_block(&[1, 2, 3]), Err(BlockCheckError::BadList));
+    }
+    #[cfg(feature = "reqwest")]
+    fn http_ok_response(body: &str, etag: Option<&str>) -> String {
+        let etag_header = etag
+            .map(|etag| format!("ETag: {etag}\r\n"))
+            .unwrap_or_default();
+        format!(
+            "HTTP/1.1 200 OK\r\n{etag_header}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+    /// Binds then immediately releases a local port, so connecting to it fails fast and
+    /// deterministically with a connection-refused error.
+    #[cfg(feature = "reqwest")]
+    fn dead_server_url() -> reqwest::Url {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        reqwest::Url::parse(&format!("http://{addr}/")).unwrap()
+    }
+    /// Spawns a background thread that replies to each incoming connection, in order, with the
+    /// next of `responses` (raw HTTP bytes) and then closes the connection. Returns the server's
+    /// URL and a counter of how many connections it has accepted so far.
+    #[cfg(feature = "reqwest")]
+    fn spawn_test_server(responses: Vec<String>) -> (reqwest::Url, Arc<AtomicUsize>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let hits_thread = hits.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let index = hits_thread.fetch_add(1, Ordering::SeqCst);
+                if let Some(response) = responses.get(index) {
+                    let _ = stream.write_all(response.as_bytes());
+                }
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }
+        });
+        let url = reqwest::Url::parse(&format!("http://{addr}/")).unwrap();
+        (url, hits)
+    }
+    fn new_test_genner_with_seed_length(seed_length: usize) -> AdminGenerator {
+        let params = LicenseStructParameters {
+            seed_length,
+            payload_length: 10,
+            chunk_size: 2,
+            validity_enabled: false,
+            #[cfg(feature = "ed25519")]
+            ed25519_enabled: false,
+            #[cfg(feature = "ed25519")]
+            ed25519_chain_enabled: false,
+        };
+        AdminGenerator::new_with_random_ivs(params)
     }
     fn new_test_genner() -> AdminGenerator {
         let params = LicenseStructParameters {
             seed_length: 6,
             payload_length: 10,
             chunk_size: 2,
+            validity_enabled: false,
+            #[cfg(feature = "ed25519")]
+            ed25519_enabled: false,
+            #[cfg(feature = "ed25519")]
+            ed25519_chain_enabled: false,
         };
-        let genner = AdminGenerator::new_with_random_ivs(params);
-        genner
+        AdminGenerator::new_with_random_ivs(params)
+    }
+    fn new_test_genner_with_validity() -> AdminGenerator {
+        let params = LicenseStructParameters {
+            seed_length: 6,
+            payload_length: 10,
+            chunk_size: 2,
+            validity_enabled: true,
+            #[cfg(feature = "ed25519")]
+            ed25519_enabled: false,
+            #[cfg(feature = "ed25519")]
+            ed25519_chain_enabled: false,
+        };
+        AdminGenerator::new_with_random_ivs(params)
     }
     fn new_test_license() -> License {
         let genner = new_test_genner();
         genner
-            .generate_license(vec![5, 100, 42, 69, 3, 90])
+            .generate_license(vec![5, 100, 42, 69, 3, 90], None)
             .unwrap()
     }
 }
@@ -0,0 +1,120 @@
+//! Structured seeds: instead of packing/unpacking a raw `Vec<u8>` by hand, build a seed from
+//! named fields (a customer id and a [`LicenseType`] tier) with `SeedBuilder`, and decode those
+//! same fields back out of a verified license with `License::decoded_seed`.
+use crate::check::License;
+use thiserror::Error;
+
+/// The tier a license was issued for. Software can match on this to gate features by edition
+/// without a second lookup. New tiers can be added as variants; unrecognized wire values decode
+/// to `Custom` so older code can still round-trip a seed it doesn't know the tier of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LicenseType {
+    Trial,
+    Standard,
+    Pro,
+    Site,
+    /// A tier not among the named variants above, keyed by its raw wire value. Must be greater
+    /// than `Self::MAX_NAMED_VALUE`: a value that collides with a named variant's wire value
+    /// would decode back as that variant, not `Custom`, breaking the round trip.
+    Custom(u8),
+}
+
+impl LicenseType {
+    /// The highest wire value occupied by a named variant; `Custom` values must exceed this.
+    const MAX_NAMED_VALUE: u8 = 3;
+
+    fn to_byte(self) -> u8 {
+        match self {
+            LicenseType::Trial => 0,
+            LicenseType::Standard => 1,
+            LicenseType::Pro => 2,
+            LicenseType::Site => 3,
+            LicenseType::Custom(value) => value,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            0 => LicenseType::Trial,
+            1 => LicenseType::Standard,
+            2 => LicenseType::Pro,
+            3 => LicenseType::Site,
+            value => LicenseType::Custom(value),
+        }
+    }
+}
+
+/// Builds a seed with a deterministic fixed-width layout (`license_type || customer_id`,
+/// zero-padded out to `seed_length`), so an admin doesn't have to pack raw bytes by hand.
+#[derive(Debug, Clone, Copy)]
+pub struct SeedBuilder {
+    pub license_type: LicenseType,
+    pub customer_id: u64,
+}
+
+impl SeedBuilder {
+    /// Bytes a packed `SeedBuilder` occupies: 1 for the license type, 8 for the customer id.
+    pub const ENCODED_LEN: usize = 1 + 8;
+
+    pub fn new(license_type: LicenseType, customer_id: u64) -> Self {
+        Self {
+            license_type,
+            customer_id,
+        }
+    }
+
+    /// Packs this builder into a seed exactly `seed_length` bytes long, erroring if the fields
+    /// don't fit. Remaining bytes beyond `Self::ENCODED_LEN` are zero-padded.
+    pub fn build(&self, seed_length: usize) -> Result<Vec<u8>, SeedError> {
+        if seed_length < Self::ENCODED_LEN {
+            return Err(SeedError::TooShort {
+                required: Self::ENCODED_LEN,
+                actual: seed_length,
+            });
+        }
+        if let LicenseType::Custom(value) = self.license_type {
+            if value <= LicenseType::MAX_NAMED_VALUE {
+                return Err(SeedError::AmbiguousCustomType(value));
+            }
+        }
+        let mut seed = vec![0u8; seed_length];
+        seed[0] = self.license_type.to_byte();
+        seed[1..1 + 8].copy_from_slice(&self.customer_id.to_be_bytes());
+        Ok(seed)
+    }
+}
+
+/// The fields decoded back out of a structured seed; see `License::decoded_seed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedSeed {
+    pub license_type: LicenseType,
+    pub customer_id: u64,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SeedError {
+    #[error("seed is too short to hold a structured layout: need at least {required} bytes, got {actual}")]
+    TooShort { required: usize, actual: usize },
+    /// `LicenseType::Custom(value)` with `value` equal to a named variant's wire value; it would
+    /// decode back as that named variant instead of `Custom`, not round-tripping faithfully.
+    #[error("LicenseType::Custom({0}) collides with a named variant's wire value and wouldn't round-trip")]
+    AmbiguousCustomType(u8),
+}
+
+impl License {
+    /// Decodes the fields a `SeedBuilder` packed into this license's seed. Only meaningful for
+    /// licenses generated from a `SeedBuilder`; a raw hand-packed seed will decode to whatever
+    /// its first 9 bytes happen to contain.
+    pub fn decoded_seed(&self) -> Result<DecodedSeed, SeedError> {
+        if self.seed.len() < SeedBuilder::ENCODED_LEN {
+            return Err(SeedError::TooShort {
+                required: SeedBuilder::ENCODED_LEN,
+                actual: self.seed.len(),
+            });
+        }
+        Ok(DecodedSeed {
+            license_type: LicenseType::from_byte(self.seed[0]),
+            customer_id: u64::from_be_bytes(self.seed[1..1 + 8].try_into().unwrap()),
+        })
+    }
+}